@@ -0,0 +1,52 @@
+//! Configuration for the relayer service.
+
+use crate::service::subscription::IngestionMode;
+use ethers_core::types::H160;
+use fuel_core_types::blockchain::primitives::DaBlockHeight;
+use std::time::Duration;
+use url::Url;
+
+/// Configuration settings for the relayer service, passed in from the
+/// node's top-level configuration.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Eth client endpoints to use, tried in priority order: the first is
+    /// selected on startup and the rest are only used as failover.
+    pub eth_client_urls: Vec<Url>,
+    /// Timeout applied to every individual request made against an eth
+    /// endpoint through the HTTP endpoint pool, so a wedged endpoint can't
+    /// stall the relayer forever. In subscription mode it bounds
+    /// `get_block_number` and each `get_logs` page; the websocket connect,
+    /// the head subscription and the reorg check's block lookups are not
+    /// bounded by it.
+    pub eth_request_timeout: Duration,
+    /// How the relayer ingests DA logs: polling page-by-page, or
+    /// subscribing to a websocket endpoint and falling back to polling.
+    pub ingestion_mode: IngestionMode,
+    /// Contract addresses to filter DA event logs by.
+    pub eth_v2_listening_contracts: Vec<H160>,
+    /// Height to start watching the DA layer from if nothing has been
+    /// synced yet.
+    pub da_deploy_height: DaBlockHeight,
+    /// Number of confirmations an eth block needs before it's considered
+    /// finalized and safe to import.
+    pub da_finalization: DaBlockHeight,
+    /// Number of DA blocks to request logs for per page.
+    pub log_page_size: u64,
+    /// Minimum duration between iterations of the normal polling loop.
+    pub sync_minimum_duration: Duration,
+    /// How often to poll `eth_syncing` while waiting for the eth node to
+    /// finish syncing.
+    pub syncing_call_frequency: Duration,
+    /// How often to log progress while waiting for the eth node to finish
+    /// syncing.
+    pub syncing_log_frequency: Duration,
+    /// The initial gap between the finalized DA height and the eth head
+    /// has to be at least this large for the no-sleep catch-up loop to
+    /// run at all; below it, the relayer just signals caught up and falls
+    /// through to the normal polling loop.
+    pub initial_sync_gap_threshold: u64,
+    /// The no-sleep catch-up loop keeps paging until the remaining gap
+    /// falls to this margin, then signals caught up.
+    pub initial_sync_margin: u64,
+}
@@ -0,0 +1,7 @@
+//! Syncs data from the DA layer to the fuel node.
+
+pub mod config;
+pub mod ports;
+pub mod service;
+
+pub use config::Config;
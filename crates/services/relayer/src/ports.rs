@@ -0,0 +1,39 @@
+//! Database access the relayer needs, independent of the storage backend.
+
+use ethers_core::types::H256;
+use fuel_core_types::blockchain::primitives::DaBlockHeight;
+
+/// Database methods the relayer service needs from the fuel node's
+/// storage, beyond the [`Messages`](fuel_core_storage::tables::Messages)
+/// table access it already gets through `StorageInspect`/`StorageMutate`.
+pub trait RelayerDb: Send + Sync {
+    /// Get the finalized da height that represents the last block from the
+    /// da layer that got finalized.
+    fn get_finalized_da_height(&self) -> anyhow::Result<DaBlockHeight>;
+
+    /// Set the finalized da height to `height`, unless it's already at
+    /// least that high.
+    fn set_finalized_da_height_to_at_least(
+        &mut self,
+        height: &DaBlockHeight,
+    ) -> anyhow::Result<()>;
+
+    /// Get the canonical eth block hash stored for `height`, if any has
+    /// been persisted yet.
+    fn get_finalized_da_block_hash(
+        &self,
+        height: &DaBlockHeight,
+    ) -> anyhow::Result<Option<H256>>;
+
+    /// Persist the canonical eth block hash for `height`.
+    fn set_finalized_da_block_hash(
+        &mut self,
+        height: &DaBlockHeight,
+        hash: H256,
+    ) -> anyhow::Result<()>;
+
+    /// Roll the finalized da height and every DA message imported above
+    /// `height` back out of storage, used to recover from a detected eth
+    /// reorg.
+    fn rollback_finalized_da_height_to(&mut self, height: &DaBlockHeight) -> anyhow::Result<()>;
+}
@@ -48,12 +48,17 @@ use tokio::sync::watch;
 
 use self::{
     get_logs::*,
+    provider_pool::ProviderPool,
     run::RelayerData,
+    subscription::IngestionMode,
 };
 
 mod get_logs;
+mod provider_pool;
+mod reorg;
 mod run;
 mod state;
+pub(crate) mod subscription;
 mod synced;
 mod syncing;
 
@@ -62,9 +67,11 @@ mod test;
 
 type Synced = watch::Receiver<Option<DaBlockHeight>>;
 type NotifySynced = watch::Sender<Option<DaBlockHeight>>;
+type CaughtUp = watch::Receiver<bool>;
+type NotifyCaughtUp = watch::Sender<bool>;
 
 /// The alias of runnable relayer service.
-pub type Service<D> = CustomizableService<Provider<Http>, D>;
+pub type Service<D> = CustomizableService<ProviderPool<Provider<Http>>, D>;
 type CustomizableService<P, D> = ServiceRunner<Task<P, D>>;
 
 /// The shared state of the relayer task.
@@ -72,6 +79,9 @@ type CustomizableService<P, D> = ServiceRunner<Task<P, D>>;
 pub struct SharedState<D> {
     /// Receives signals when the relayer reaches consistency with the DA layer.
     synced: Synced,
+    /// Receives a one-shot signal when the relayer finishes its initial
+    /// catch-up with the DA layer.
+    caught_up: CaughtUp,
     database: D,
 }
 
@@ -80,6 +90,9 @@ pub struct SharedState<D> {
 pub struct Task<P, D> {
     /// Sends signals when the relayer reaches consistency with the DA layer.
     synced: NotifySynced,
+    /// Sends a one-shot signal once the relayer finishes its initial
+    /// catch-up with the DA layer.
+    caught_up: NotifyCaughtUp,
     /// The node that communicates with Ethereum.
     eth_node: Arc<P>,
     /// The fuel database.
@@ -90,9 +103,16 @@ pub struct Task<P, D> {
 
 impl<P, D> Task<P, D> {
     /// Create a new relayer task.
-    fn new(synced: NotifySynced, eth_node: P, database: D, config: Config) -> Self {
+    fn new(
+        synced: NotifySynced,
+        caught_up: NotifyCaughtUp,
+        eth_node: P,
+        database: D,
+        config: Config,
+    ) -> Self {
         Self {
             synced,
+            caught_up,
             eth_node: Arc::new(eth_node),
             database,
             config,
@@ -110,6 +130,68 @@ where
             .set_finalized_da_height_to_at_least(&self.config.da_deploy_height)
             .expect("Should be able to set the finalized da height");
     }
+
+    /// Close the gap between the finalized DA height and the eth head
+    /// page-by-page, without the inter-iteration sleep, until the
+    /// remaining gap falls to or below `initial_sync_margin`. Emits a one-shot
+    /// completion signal on [`Task::caught_up`] so services that need the
+    /// bridge fully backfilled can wait on [`SharedState::await_initial_catch_up`].
+    async fn run_catch_up(&mut self, watcher: &mut StateWatcher) -> anyhow::Result<()> {
+        if *self.caught_up.borrow() {
+            return Ok(())
+        }
+
+        let gap = tokio::select! {
+            biased;
+            _ = watcher.while_started() => return Ok(()),
+            gap = self.remaining_sync_gap() => gap?,
+        };
+        if gap < self.config.initial_sync_gap_threshold {
+            let _ = self.caught_up.send(true);
+            return Ok(())
+        }
+
+        loop {
+            let gap = tokio::select! {
+                biased;
+                _ = watcher.while_started() => return Ok(()),
+                gap = self.remaining_sync_gap() => gap?,
+            };
+
+            if gap <= self.config.initial_sync_margin {
+                break
+            }
+
+            let finalized = self.finalized().unwrap_or(0);
+            let page_end = catch_up_page_end(finalized, gap, self.config.log_page_size);
+            let eth_sync_gap = state::EthSyncGap::new(finalized, page_end);
+            self.download_logs(&eth_sync_gap, watcher).await?;
+        }
+
+        tracing::info!("relayer finished its initial catch-up with the DA layer");
+        let _ = self.caught_up.send(true);
+        Ok(())
+    }
+
+    /// The gap between the finalized DA height and the eth head that has
+    /// actually cleared `finalization_period`, matching the normal
+    /// polling path so catch-up never imports unfinalized logs.
+    async fn remaining_sync_gap(&self) -> anyhow::Result<u64> {
+        let head = <Self as state::EthRemote>::current(self).await?;
+        let finalized_head =
+            head.saturating_sub(<Self as state::EthRemote>::finalization_period(self));
+        let finalized = self.finalized().unwrap_or(0);
+        Ok(finalized_head.saturating_sub(finalized))
+    }
+}
+
+/// The end of the next catch-up page starting at `finalized`: capped at
+/// `log_page_size` blocks so a single page never balloons past it, and at
+/// `gap` so the page never reaches past the eth head that was checked.
+fn catch_up_page_end(finalized: u64, gap: u64, log_page_size: u64) -> u64 {
+    finalized
+        .saturating_add(log_page_size)
+        .min(finalized.saturating_add(gap))
 }
 
 #[async_trait]
@@ -118,26 +200,54 @@ where
     P: Middleware<Error = ProviderError> + 'static,
     D: RelayerDb + 'static,
 {
-    async fn wait_if_eth_syncing(&self) -> anyhow::Result<()> {
-        syncing::wait_if_eth_syncing(
-            &self.eth_node,
-            self.config.syncing_call_frequency,
-            self.config.syncing_log_frequency,
-        )
-        .await
+    async fn wait_if_eth_syncing(
+        &self,
+        watcher: &mut StateWatcher,
+    ) -> anyhow::Result<()> {
+        tokio::select! {
+            biased;
+            _ = watcher.while_started() => Ok(()),
+            result = syncing::wait_if_eth_syncing(
+                &self.eth_node,
+                self.config.syncing_call_frequency,
+                self.config.syncing_log_frequency,
+                self.config.eth_request_timeout,
+            ) => result,
+        }
     }
 
     async fn download_logs(
         &mut self,
         eth_sync_gap: &state::EthSyncGap,
+        watcher: &mut StateWatcher,
     ) -> anyhow::Result<()> {
+        let eth_sync_gap =
+            reorg::reconcile_reorg(self.eth_node.as_ref(), &mut self.database, *eth_sync_gap)
+                .await?;
         let logs = download_logs(
-            eth_sync_gap,
+            &eth_sync_gap,
             self.config.eth_v2_listening_contracts.clone(),
             self.eth_node.clone(),
             self.config.log_page_size,
+            self.config.eth_request_timeout,
         );
-        write_logs(&mut self.database, logs).await
+        tokio::select! {
+            biased;
+            _ = watcher.while_started() => return Ok(()),
+            result = write_logs(&mut self.database, logs) => result?,
+        };
+        self.database.set_finalized_da_height_to_at_least(&DaBlockHeight::from(
+            eth_sync_gap.latest(),
+        ))?;
+
+        reorg::persist_finalized_hashes(
+            self.eth_node.as_ref(),
+            &mut self.database,
+            eth_sync_gap.oldest(),
+            eth_sync_gap.latest(),
+        )
+        .await?;
+        Ok(())
     }
 
     fn update_synced(&self, state: &state::EthState) {
@@ -158,9 +268,11 @@ where
 
     fn shared_data(&self) -> Self::SharedData {
         let synced = self.synced.subscribe();
+        let caught_up = self.caught_up.subscribe();
 
         SharedState {
             synced,
+            caught_up,
             database: self.database.clone(),
         }
     }
@@ -177,21 +289,49 @@ where
     P: Middleware<Error = ProviderError> + 'static,
     D: RelayerDb + 'static,
 {
-    async fn run(&mut self, _watcher: &mut StateWatcher) -> anyhow::Result<bool> {
+    async fn run(&mut self, watcher: &mut StateWatcher) -> anyhow::Result<bool> {
+        if let IngestionMode::Subscription { ws_url } = self.config.ingestion_mode.clone() {
+            // The subscription loop owns its own lifetime; it only returns
+            // once the watcher stops, or with an error once the socket has
+            // dropped `MAX_RECONNECT_ATTEMPTS` times, in which case this
+            // iteration falls back to polling.
+            match subscription::run_subscription(
+                &ws_url,
+                self.config.eth_v2_listening_contracts.clone(),
+                self.config.log_page_size,
+                *self.config.da_finalization,
+                self.config.eth_request_timeout,
+                &mut self.database,
+                &self.synced,
+                &self.caught_up,
+                watcher,
+            )
+            .await
+            {
+                Ok(()) => return Ok(false),
+                Err(err) => {
+                    tracing::warn!(%err, "falling back to polling for this iteration");
+                }
+            }
+        }
+
+        self.run_catch_up(watcher).await?;
+
         let now = tokio::time::Instant::now();
         let should_continue = true;
 
-        // TODO: Pass `_watcher` into `Task` to handle graceful shutdown for
-        //  `download_logs`, `wait_if_eth_syncing`, `build_eth` methods.
-        //  Otherwise, the shutdown process can take a lot of time.
-        let result = run::run(self).await;
-        // Sleep the loop so the da node is not spammed.
-        tokio::time::sleep(
-            self.config
-                .sync_minimum_duration
-                .saturating_sub(now.elapsed()),
-        )
-        .await;
+        let result = run::run(self, watcher).await;
+        // Sleep the loop so the da node is not spammed, but don't let a stop
+        // signal wait out the whole sleep.
+        tokio::select! {
+            biased;
+            _ = watcher.while_started() => {}
+            _ = tokio::time::sleep(
+                self.config
+                    .sync_minimum_duration
+                    .saturating_sub(now.elapsed()),
+            ) => {}
+        }
 
         result.map(|_| should_continue)
     }
@@ -217,6 +357,20 @@ impl<D> SharedState<D> {
         Ok(())
     }
 
+    /// Wait for the [`Task`] to finish its initial catch-up with the DA
+    /// layer, distinct from [`Self::await_synced`]: the signal is sent
+    /// once, after the relayer has closed a large initial backfill gap (or
+    /// right away if the initial gap was below `initial_sync_gap_threshold`),
+    /// and never fires again afterwards. Lets callers defer enabling
+    /// services that require the bridge to be fully backfilled.
+    pub async fn await_initial_catch_up(&self) -> anyhow::Result<()> {
+        let mut rx = self.caught_up.clone();
+        if !*rx.borrow_and_update() {
+            rx.changed().await?;
+        }
+        Ok(())
+    }
+
     /// Wait until at least the given height is synced.
     pub async fn await_at_least_synced(
         &self,
@@ -264,7 +418,13 @@ where
     D: RelayerDb + 'static,
 {
     async fn current(&self) -> anyhow::Result<u64> {
-        Ok(self.eth_node.get_block_number().await?.as_u64())
+        // `eth_node` is a `ProviderPool`, which already bounds every
+        // per-endpoint attempt by `eth_request_timeout` and fails over to
+        // the next endpoint on timeout; wrapping the whole call in another
+        // timeout of the same length would trip before failover ever got
+        // to try a second endpoint.
+        let height = self.eth_node.get_block_number().await?;
+        Ok(height.as_u64())
     }
 
     fn finalization_period(&self) -> u64 {
@@ -288,14 +448,23 @@ pub fn new_service<D>(database: D, config: Config) -> anyhow::Result<Service<D>>
 where
     D: RelayerDb + Clone + 'static,
 {
-    let url = config.eth_client.clone().ok_or_else(|| {
-        anyhow::anyhow!(
+    if config.eth_client_urls.is_empty() {
+        return Err(anyhow::anyhow!(
             "Tried to start Relayer without setting an eth_client in the config"
-        )
-    })?;
+        ))
+    }
     // TODO: Does this handle https?
-    let http = Http::new(url);
-    let eth_node = Provider::new(http);
+    let providers = config
+        .eth_client_urls
+        .iter()
+        .cloned()
+        .map(|url| Provider::new(Http::new(url)))
+        .collect();
+    let eth_node = ProviderPool::new(
+        providers,
+        config.sync_minimum_duration,
+        config.eth_request_timeout,
+    );
     Ok(new_service_internal(eth_node, database, config))
 }
 
@@ -323,7 +492,8 @@ where
     D: RelayerDb + Clone + 'static,
 {
     let (tx, _) = watch::channel(None);
-    let task = Task::new(tx, eth_node, database, config);
+    let (caught_up_tx, _) = watch::channel(false);
+    let task = Task::new(tx, caught_up_tx, eth_node, database, config);
 
     CustomizableService::new(task)
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+//! Download DA event logs for a sync gap and persist any messages found
+//! in them.
+
+use super::state::EthSyncGap;
+use crate::{
+    log::EthEventLog,
+    ports::RelayerDb,
+};
+use ethers_core::types::{
+    Filter,
+    Log,
+    ValueOrArray,
+    H160,
+};
+use ethers_providers::{
+    Middleware,
+    ProviderError,
+};
+use fuel_core_storage::{
+    tables::Messages,
+    StorageAsMut,
+};
+use futures::stream::{
+    self,
+    Stream,
+    StreamExt,
+};
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+/// Page through `eth_sync_gap` in chunks of `log_page_size`, fetching
+/// each page's logs in turn. Each page's `get_logs` call is bounded by
+/// `request_timeout` on its own, so a wedged endpoint stalls at most one
+/// page instead of the whole catch-up.
+pub fn download_logs<P>(
+    eth_sync_gap: &EthSyncGap,
+    contracts: Vec<H160>,
+    eth_node: Arc<P>,
+    log_page_size: u64,
+    request_timeout: Duration,
+) -> impl Stream<Item = anyhow::Result<Vec<Log>>>
+where
+    P: Middleware<Error = ProviderError> + 'static,
+{
+    let filter = Filter::new().address(ValueOrArray::Array(contracts));
+    let latest = eth_sync_gap.latest();
+
+    stream::unfold(Some(eth_sync_gap.oldest()), move |state| {
+        let filter = filter.clone();
+        let eth_node = eth_node.clone();
+        async move {
+            let page_start = state?;
+            let page_end = page_start
+                .saturating_add(log_page_size.saturating_sub(1))
+                .min(latest);
+            let page_filter = filter.from_block(page_start).to_block(page_end);
+
+            let result = tokio::time::timeout(request_timeout, eth_node.get_logs(&page_filter))
+                .await
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "get_logs for DA blocks {page_start}..={page_end} timed out after {request_timeout:?}"
+                    )
+                })
+                .and_then(|logs| logs.map_err(anyhow::Error::from));
+
+            let next_state = (page_end < latest).then(|| page_end.saturating_add(1));
+            Some((result, next_state))
+        }
+    })
+}
+
+/// Write every log page in `pages` to the database as DA messages.
+pub async fn write_logs<D>(
+    database: &mut D,
+    mut pages: impl Stream<Item = anyhow::Result<Vec<Log>>> + Unpin,
+) -> anyhow::Result<()>
+where
+    D: RelayerDb + StorageAsMut,
+{
+    while let Some(page) = pages.next().await {
+        for log in page? {
+            let event = EthEventLog::try_from(&log)?;
+            if let Some(message) = event.message() {
+                database
+                    .storage::<Messages>()
+                    .insert(message.id(), message)?;
+            }
+        }
+    }
+    Ok(())
+}
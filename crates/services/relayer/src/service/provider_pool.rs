@@ -0,0 +1,404 @@
+//! A pool of Ethereum endpoints that fails over between them and tracks
+//! their health.
+//!
+//! The pool presents the same [`Middleware`] interface as a single
+//! provider, so callers such as [`download_logs`](super::get_logs::download_logs)
+//! and [`EthRemote::current`](super::state::EthRemote::current) don't need
+//! to know they're talking to more than one endpoint.
+
+use async_trait::async_trait;
+use core::time::Duration;
+use ethers_core::types::{
+    Block,
+    BlockId,
+    Filter,
+    Log,
+    TxHash,
+    U64,
+};
+use ethers_providers::{
+    Middleware,
+    ProviderError,
+};
+use std::sync::{
+    atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+    Arc,
+    Mutex,
+    Weak,
+};
+use tokio::task::JoinHandle;
+use tracing::{
+    info,
+    warn,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct EndpointHealth {
+    healthy: bool,
+    last_success: Option<tokio::time::Instant>,
+}
+
+impl Default for EndpointHealth {
+    fn default() -> Self {
+        Self {
+            healthy: true,
+            last_success: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoolInner<P> {
+    /// Endpoints ordered by priority; index `0` is tried first.
+    providers: Vec<P>,
+    health: Vec<Mutex<EndpointHealth>>,
+    /// Index of the endpoint that should be tried first on the next request.
+    selected: AtomicUsize,
+    /// Applied to every per-endpoint request, including watchdog pings, so
+    /// a wedged endpoint that accepts the connection but never responds
+    /// gets demoted rather than blocking the caller.
+    endpoint_timeout: Duration,
+    /// Aborted when the last handle to the pool is dropped.
+    watchdog: JoinHandle<()>,
+}
+
+impl<P> Drop for PoolInner<P> {
+    fn drop(&mut self) {
+        self.watchdog.abort();
+    }
+}
+
+impl<P> PoolInner<P>
+where
+    P: Middleware<Error = ProviderError>,
+{
+    fn selected_index(&self) -> usize {
+        self.selected.load(Ordering::Relaxed)
+    }
+
+    fn record_success(&self, index: usize) {
+        let Some(health) = self.health.get(index) else {
+            return
+        };
+        let mut health = health.lock().expect("endpoint health lock poisoned");
+        let recovered = !health.healthy;
+        health.healthy = true;
+        health.last_success = Some(tokio::time::Instant::now());
+        if recovered {
+            info!(endpoint = index, "eth endpoint recovered, marking healthy");
+        }
+    }
+
+    /// Mark an endpoint unhealthy and immediately fail the active request
+    /// over to the next one in priority order. Only appropriate when
+    /// `failed` is the endpoint a caller is actually waiting on right now;
+    /// the watchdog sweep uses [`Self::mark_unhealthy`] instead, since it
+    /// pings every endpoint regardless of which one is selected.
+    fn demote(&self, failed: usize) {
+        let (became_unhealthy, last_success) = self.mark_unhealthy(failed);
+        let next = (failed + 1) % self.providers.len();
+        self.selected.store(next, Ordering::Relaxed);
+        if became_unhealthy {
+            warn!(
+                endpoint = failed,
+                next,
+                last_success_secs_ago = last_success.map(|t| t.elapsed().as_secs()),
+                "eth endpoint marked unhealthy, failing over"
+            );
+        }
+    }
+
+    /// Record an endpoint as unhealthy without touching `selected`. Used by
+    /// the watchdog sweep, which pings every endpoint once per tick
+    /// regardless of which one is active: reassigning `selected` here would
+    /// let a known-bad backup's periodic ping keep bumping the pool away
+    /// from a healthy, higher-priority endpoint that never actually failed.
+    /// Returns whether the endpoint just transitioned to unhealthy, and its
+    /// last recorded success time, for logging.
+    fn mark_unhealthy(&self, index: usize) -> (bool, Option<tokio::time::Instant>) {
+        let Some(health) = self.health.get(index) else {
+            return (false, None)
+        };
+        let mut health = health.lock().expect("endpoint health lock poisoned");
+        let was_healthy = health.healthy;
+        health.healthy = false;
+        (was_healthy, health.last_success)
+    }
+
+    fn is_healthy(&self, index: usize) -> bool {
+        self.health
+            .get(index)
+            .map(|health| health.lock().expect("endpoint health lock poisoned").healthy)
+            .unwrap_or(false)
+    }
+
+    /// Re-point `selected` at the lowest-index healthy endpoint, restoring
+    /// priority order once an earlier endpoint recovers. Falls back to
+    /// endpoint `0` if none are currently healthy, since some endpoint has
+    /// to be tried next regardless.
+    fn recompute_selected(&self) {
+        let selected = (0..self.providers.len())
+            .find(|&index| self.is_healthy(index))
+            .unwrap_or(0);
+        self.selected.store(selected, Ordering::Relaxed);
+    }
+
+    /// Run a single request against one endpoint, bounded by
+    /// `endpoint_timeout` so a wedged endpoint can't block the caller
+    /// forever.
+    async fn call_one<F, Fut, T>(&self, index: usize, request: F) -> Result<T, ProviderError>
+    where
+        F: FnOnce(&P) -> Fut,
+        Fut: std::future::Future<Output = Result<T, ProviderError>>,
+    {
+        match tokio::time::timeout(self.endpoint_timeout, request(&self.providers[index])).await {
+            Ok(result) => result,
+            Err(_) => Err(ProviderError::CustomError(format!(
+                "eth endpoint {index} timed out after {:?}",
+                self.endpoint_timeout
+            ))),
+        }
+    }
+
+    /// Try a request against every endpoint in priority order, starting
+    /// from the currently selected one, demoting each one that errors or
+    /// times out and promoting the first one that succeeds.
+    async fn try_each<F, Fut, T>(&self, mut request: F) -> Result<T, ProviderError>
+    where
+        F: FnMut(&P) -> Fut,
+        Fut: std::future::Future<Output = Result<T, ProviderError>>,
+    {
+        let start = self.selected_index();
+        let len = self.providers.len();
+        let mut last_err = None;
+        for offset in 0..len {
+            let index = (start + offset) % len;
+            match self.call_one(index, &mut request).await {
+                Ok(value) => {
+                    self.record_success(index);
+                    return Ok(value)
+                }
+                Err(err) => {
+                    self.demote(index);
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err.expect("providers is non-empty"))
+    }
+
+    /// Ping every endpoint once, independent of which one is currently
+    /// selected, and refresh its health state and last-success time. Each
+    /// ping is individually bounded by `endpoint_timeout` so one wedged
+    /// endpoint can't stall the rest of the sweep.
+    ///
+    /// `selected` is only recomputed once, after the full sweep, as the
+    /// lowest-index healthy endpoint — never by index-arithmetic off of
+    /// whichever endpoint the sweep happens to be pinging, so a
+    /// persistently down backup can't keep bumping a healthy, higher
+    /// priority endpoint out of rotation.
+    async fn run_upcheck(&self) {
+        let before = self.selected_index();
+        for index in 0..self.providers.len() {
+            match self.call_one(index, |provider| provider.get_block_number()).await {
+                Ok(_) => self.record_success(index),
+                Err(_) => {
+                    let (became_unhealthy, last_success) = self.mark_unhealthy(index);
+                    if became_unhealthy {
+                        warn!(
+                            endpoint = index,
+                            last_success_secs_ago = last_success.map(|t| t.elapsed().as_secs()),
+                            "eth endpoint marked unhealthy"
+                        );
+                    }
+                }
+            }
+        }
+        self.recompute_selected();
+        let after = self.selected_index();
+        if after != before {
+            info!(from = before, to = after, "watchdog switched active eth endpoint");
+        }
+    }
+}
+
+/// A [`Middleware`] implementation backed by a prioritised list of
+/// Ethereum endpoints.
+///
+/// Every request is attempted against the currently selected endpoint
+/// immediately, regardless of its cached health, so a single transient
+/// failure can't stall syncing by waiting on a watchdog tick. A background
+/// watchdog separately pings every endpoint once per `upcheck_interval`
+/// to keep the health state fresh, and after each sweep re-selects the
+/// highest-priority healthy endpoint.
+#[derive(Debug, Clone)]
+pub struct ProviderPool<P> {
+    inner: Arc<PoolInner<P>>,
+}
+
+impl<P> ProviderPool<P>
+where
+    P: Middleware<Error = ProviderError> + 'static,
+{
+    /// Create a pool from a list of endpoints ordered by priority and start
+    /// its background watchdog, which upchecks every endpoint once per
+    /// `upcheck_interval`, bounding each ping by `endpoint_timeout`.
+    ///
+    /// The watchdog only holds a [`Weak`] handle to the pool, so it exits
+    /// on its own once the last strong handle is dropped instead of
+    /// keeping the pool alive forever.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `providers` is empty.
+    pub fn new(providers: Vec<P>, upcheck_interval: Duration, endpoint_timeout: Duration) -> Self {
+        assert!(
+            !providers.is_empty(),
+            "ProviderPool requires at least one eth client endpoint"
+        );
+        let health = providers
+            .iter()
+            .map(|_| Mutex::new(EndpointHealth::default()))
+            .collect();
+
+        let inner = Arc::new_cyclic(|weak: &Weak<PoolInner<P>>| {
+            let weak = weak.clone();
+            let watchdog = tokio::spawn(async move {
+                let mut interval = tokio::time::interval(upcheck_interval);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    interval.tick().await;
+                    let Some(inner) = weak.upgrade() else {
+                        // Every strong handle to the pool has been
+                        // dropped; nothing left to upcheck.
+                        return
+                    };
+                    inner.run_upcheck().await;
+                }
+            });
+
+            PoolInner {
+                providers,
+                health,
+                selected: AtomicUsize::new(0),
+                endpoint_timeout,
+                watchdog,
+            }
+        });
+
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<P> Middleware for ProviderPool<P>
+where
+    P: Middleware<Error = ProviderError> + 'static,
+{
+    type Error = ProviderError;
+    type Provider = P::Provider;
+    type Inner = P;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner.providers[self.inner.selected_index()]
+    }
+
+    async fn get_block_number(&self) -> Result<U64, Self::Error> {
+        self.inner
+            .try_each(|provider| provider.get_block_number())
+            .await
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        self.inner
+            .try_each(|provider| provider.get_logs(filter))
+            .await
+    }
+
+    async fn get_block<T>(&self, block_hash_or_number: T) -> Result<Option<Block<TxHash>>, Self::Error>
+    where
+        T: Into<BlockId> + Send + Sync,
+    {
+        let block_id = block_hash_or_number.into();
+        self.inner
+            .try_each(move |provider| provider.get_block(block_id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{
+        MockProvider,
+        Provider,
+    };
+
+    fn pool_of(n: usize) -> (ProviderPool<Provider<MockProvider>>, Vec<MockProvider>) {
+        let mocks: Vec<MockProvider> = (0..n).map(|_| MockProvider::new()).collect();
+        let providers = mocks.iter().cloned().map(Provider::new).collect();
+        let pool = ProviderPool::new(providers, Duration::from_secs(3600), Duration::from_millis(200));
+        (pool, mocks)
+    }
+
+    #[tokio::test]
+    async fn try_each_fails_over_to_the_next_healthy_endpoint() {
+        let (pool, mocks) = pool_of(3);
+        // Endpoint 0 has nothing queued, so it errors; endpoint 1 answers.
+        mocks[1].push(U64::from(42)).unwrap();
+
+        let result = pool.inner.try_each(|p| p.get_block_number()).await.unwrap();
+
+        assert_eq!(result, U64::from(42));
+        assert_eq!(pool.inner.selected_index(), 1);
+    }
+
+    // Regression test for the watchdog bug: a persistently down backup's
+    // periodic ping must not bump a healthy, higher-priority endpoint out
+    // of rotation. A(healthy, idx0, selected) / B(down, idx1) / C(healthy,
+    // idx2): every sweep pings all three, but since the selected endpoint
+    // never failed, it must stay selected.
+    #[tokio::test]
+    async fn watchdog_sweep_does_not_move_selected_off_a_healthy_endpoint() {
+        let (pool, mocks) = pool_of(3);
+        mocks[0].push(U64::from(1)).unwrap();
+        // mocks[1] left empty: always errors.
+        mocks[2].push(U64::from(1)).unwrap();
+
+        pool.inner.run_upcheck().await;
+
+        assert_eq!(pool.inner.selected_index(), 0);
+    }
+
+    #[tokio::test]
+    async fn watchdog_sweep_fails_over_when_the_selected_endpoint_is_down() {
+        let (pool, mocks) = pool_of(3);
+        // mocks[0] left empty: the currently selected endpoint is down.
+        mocks[1].push(U64::from(1)).unwrap();
+        mocks[2].push(U64::from(1)).unwrap();
+
+        pool.inner.run_upcheck().await;
+
+        assert_eq!(pool.inner.selected_index(), 1);
+    }
+
+    #[tokio::test]
+    async fn watchdog_sweep_restores_priority_order_once_it_recovers() {
+        let (pool, mocks) = pool_of(2);
+        // First sweep: endpoint 0 is down, so the pool fails over to 1.
+        mocks[1].push(U64::from(1)).unwrap();
+        pool.inner.run_upcheck().await;
+        assert_eq!(pool.inner.selected_index(), 1);
+
+        // Endpoint 0 recovers; the next sweep should restore priority
+        // order instead of leaving endpoint 1 selected.
+        mocks[0].push(U64::from(2)).unwrap();
+        mocks[1].push(U64::from(2)).unwrap();
+        pool.inner.run_upcheck().await;
+
+        assert_eq!(pool.inner.selected_index(), 0);
+    }
+}
@@ -0,0 +1,317 @@
+//! Ethereum reorg detection and rollback for already-imported DA messages.
+//!
+//! [`EthRemote::finalization_period`](super::state::EthRemote::finalization_period)
+//! is the first line of defense against reorgs, but a shallow depth or a
+//! minority-fork endpoint can still let an invalid range through. This
+//! module makes the invariant "stored block hashes form an unbroken
+//! canonical chain" explicit and self-healing: before importing a new
+//! page, it checks that the parent hash of the first new block matches
+//! the hash stored for the previous height, and walks back to the common
+//! ancestor otherwise.
+
+use super::state::EthSyncGap;
+use crate::ports::RelayerDb;
+use ethers_providers::{
+    Middleware,
+    ProviderError,
+};
+use fuel_core_types::blockchain::primitives::DaBlockHeight;
+use futures::stream::{
+    self,
+    StreamExt,
+};
+use tracing::warn;
+
+/// How many `get_block` calls to run concurrently while persisting a
+/// range of hashes, so a large catch-up page doesn't serialize one RPC
+/// round-trip per height.
+const HASH_FETCH_CONCURRENCY: usize = 16;
+
+/// Check that the canonical chain hasn't forked between the last
+/// imported height and the start of `eth_sync_gap`, rolling the
+/// finalized DA height and any messages above the common ancestor back
+/// out of storage if it has. Returns the gap to actually download, which
+/// is widened to start at the ancestor when a reorg was found.
+pub async fn reconcile_reorg<P, D>(
+    eth_node: &P,
+    database: &mut D,
+    eth_sync_gap: EthSyncGap,
+) -> anyhow::Result<EthSyncGap>
+where
+    P: Middleware<Error = ProviderError>,
+    D: RelayerDb,
+{
+    let oldest = eth_sync_gap.oldest();
+    let Some(stored_hash) = database.get_finalized_da_block_hash(&DaBlockHeight::from(oldest))?
+    else {
+        // Nothing imported yet at this height, there's nothing to check
+        // the new page against.
+        return Ok(eth_sync_gap)
+    };
+
+    let first_new_height = oldest.saturating_add(1);
+    if first_new_height > eth_sync_gap.latest() {
+        // Nothing new to verify continuity against yet.
+        return Ok(eth_sync_gap)
+    }
+    let first_new_block = eth_node.get_block(first_new_height).await?.ok_or_else(|| {
+        anyhow::anyhow!("eth endpoint no longer has block {first_new_height} to verify against")
+    })?;
+    if first_new_block.parent_hash == stored_hash {
+        return Ok(eth_sync_gap)
+    }
+
+    let ancestor = find_common_ancestor(eth_node, database, oldest).await?;
+    warn!(
+        reorged_at = oldest,
+        ancestor, "detected an eth reorg, rolling back to the common ancestor"
+    );
+    database.rollback_finalized_da_height_to(&DaBlockHeight::from(ancestor))?;
+    Ok(EthSyncGap::new(ancestor, eth_sync_gap.latest()))
+}
+
+/// Walk backward from `height`, comparing stored hashes to the canonical
+/// chain, until a height is found whose stored hash still matches (or
+/// one with nothing stored, meaning it predates our records). Relies on
+/// [`persist_finalized_hashes`] having recorded a hash for every imported
+/// height, not just page boundaries, so this doesn't stop short inside a
+/// page that was itself reorged.
+async fn find_common_ancestor<P, D>(
+    eth_node: &P,
+    database: &D,
+    mut height: u64,
+) -> anyhow::Result<u64>
+where
+    P: Middleware<Error = ProviderError>,
+    D: RelayerDb,
+{
+    while height > 0 {
+        height -= 1;
+        let Some(stored_hash) = database.get_finalized_da_block_hash(&DaBlockHeight::from(height))?
+        else {
+            return Ok(height)
+        };
+        match eth_node.get_block(height).await? {
+            Some(block) if block.hash == Some(stored_hash) => return Ok(height),
+            _ => continue,
+        }
+    }
+    Ok(0)
+}
+
+/// Persist a canonical block hash for every height in `(previous, upto]`,
+/// so the stored hashes form an unbroken chain rather than only recording
+/// page boundaries. Call this after a range has been imported.
+///
+/// Fetches up to [`HASH_FETCH_CONCURRENCY`] blocks concurrently instead of
+/// one round trip at a time, since this runs once per imported page and a
+/// serial crawl would pile unnecessary load on the eth endpoints. A
+/// height whose block the endpoint no longer has, or returns without a
+/// hash, is a hard error rather than a silent skip: leaving a gap in the
+/// stored hashes would break the unbroken-chain invariant
+/// [`find_common_ancestor`] relies on.
+pub async fn persist_finalized_hashes<P, D>(
+    eth_node: &P,
+    database: &mut D,
+    previous: u64,
+    upto: u64,
+) -> anyhow::Result<()>
+where
+    P: Middleware<Error = ProviderError>,
+    D: RelayerDb,
+{
+    let mut fetches = stream::iter((previous.saturating_add(1))..=upto)
+        .map(|height| async move {
+            let block = eth_node.get_block(height).await?.ok_or_else(|| {
+                anyhow::anyhow!("eth endpoint no longer has block {height} to persist a hash for")
+            })?;
+            let hash = block.hash.ok_or_else(|| {
+                anyhow::anyhow!("eth endpoint returned block {height} without a hash")
+            })?;
+            Ok::<_, anyhow::Error>((height, hash))
+        })
+        .buffer_unordered(HASH_FETCH_CONCURRENCY);
+
+    while let Some(result) = fetches.next().await {
+        let (height, hash) = result?;
+        database.set_finalized_da_block_hash(&DaBlockHeight::from(height), hash)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{
+        Block,
+        TxHash,
+        H256,
+    };
+    use ethers_providers::{
+        MockProvider,
+        Provider,
+    };
+    use std::{
+        collections::HashMap,
+        sync::Mutex,
+    };
+
+    #[derive(Default)]
+    struct FakeDb {
+        hashes: Mutex<HashMap<u64, H256>>,
+        rolled_back_to: Mutex<Option<u64>>,
+    }
+
+    impl FakeDb {
+        fn with_hash(self, height: u64, hash: H256) -> Self {
+            self.hashes.lock().unwrap().insert(height, hash);
+            self
+        }
+    }
+
+    impl RelayerDb for FakeDb {
+        fn get_finalized_da_height(&self) -> anyhow::Result<DaBlockHeight> {
+            Ok(DaBlockHeight::from(0u64))
+        }
+
+        fn set_finalized_da_height_to_at_least(
+            &mut self,
+            _height: &DaBlockHeight,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn get_finalized_da_block_hash(
+            &self,
+            height: &DaBlockHeight,
+        ) -> anyhow::Result<Option<H256>> {
+            Ok(self.hashes.lock().unwrap().get(&u64::from(*height)).copied())
+        }
+
+        fn set_finalized_da_block_hash(
+            &mut self,
+            height: &DaBlockHeight,
+            hash: H256,
+        ) -> anyhow::Result<()> {
+            self.hashes.lock().unwrap().insert(u64::from(*height), hash);
+            Ok(())
+        }
+
+        fn rollback_finalized_da_height_to(&mut self, height: &DaBlockHeight) -> anyhow::Result<()> {
+            *self.rolled_back_to.lock().unwrap() = Some(u64::from(*height));
+            Ok(())
+        }
+    }
+
+    fn block_with(parent_hash: H256, hash: H256) -> Block<TxHash> {
+        Block {
+            parent_hash,
+            hash: Some(hash),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reconcile_reorg_leaves_the_gap_untouched_when_parents_match() {
+        let stored = H256::repeat_byte(1);
+        let mut database = FakeDb::default().with_hash(9, stored);
+        let mock = MockProvider::new();
+        mock.push(block_with(stored, H256::repeat_byte(2))).unwrap();
+        let eth_node = Provider::new(mock);
+        let gap = EthSyncGap::new(9, 12);
+
+        let result = reconcile_reorg(&eth_node, &mut database, gap).await.unwrap();
+
+        assert_eq!(result.oldest(), 9);
+        assert_eq!(result.latest(), 12);
+        assert!(database.rolled_back_to.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn reconcile_reorg_rolls_back_to_the_common_ancestor_on_mismatch() {
+        let mut database = FakeDb::default()
+            .with_hash(8, H256::repeat_byte(3))
+            .with_hash(9, H256::repeat_byte(1));
+        let mock = MockProvider::new();
+        // `MockProvider` answers with the most recently pushed response
+        // first, so responses are pushed in reverse call order.
+        // Second call: find_common_ancestor walks back to height 8, whose
+        // stored hash matches the canonical chain.
+        mock.push(block_with(H256::repeat_byte(7), H256::repeat_byte(3)))
+            .unwrap();
+        // First call: get_block(10), the first new height — parent hash
+        // doesn't match what's stored at height 9, so a reorg is detected.
+        mock.push(block_with(H256::repeat_byte(9), H256::repeat_byte(10)))
+            .unwrap();
+        let eth_node = Provider::new(mock);
+        let gap = EthSyncGap::new(9, 12);
+
+        let result = reconcile_reorg(&eth_node, &mut database, gap).await.unwrap();
+
+        assert_eq!(result.oldest(), 8);
+        assert_eq!(result.latest(), 12);
+        assert_eq!(*database.rolled_back_to.lock().unwrap(), Some(8));
+    }
+
+    #[tokio::test]
+    async fn find_common_ancestor_stops_at_the_first_matching_stored_hash() {
+        let database = FakeDb::default().with_hash(8, H256::repeat_byte(3));
+        let mock = MockProvider::new();
+        mock.push(block_with(H256::repeat_byte(7), H256::repeat_byte(3)))
+            .unwrap();
+        let eth_node = Provider::new(mock);
+
+        let ancestor = find_common_ancestor(&eth_node, &database, 9).await.unwrap();
+
+        assert_eq!(ancestor, 8);
+    }
+
+    #[tokio::test]
+    async fn find_common_ancestor_bottoms_out_at_zero_when_nothing_matches() {
+        let database = FakeDb::default().with_hash(0, H256::repeat_byte(9));
+        let mock = MockProvider::new();
+        mock.push(block_with(H256::repeat_byte(0), H256::repeat_byte(99)))
+            .unwrap();
+        let eth_node = Provider::new(mock);
+
+        let ancestor = find_common_ancestor(&eth_node, &database, 1).await.unwrap();
+
+        assert_eq!(ancestor, 0);
+    }
+
+    #[tokio::test]
+    async fn persist_finalized_hashes_stores_every_height_in_the_range() {
+        let mut database = FakeDb::default();
+        let mock = MockProvider::new();
+        // `MockProvider` answers with the most recently pushed response
+        // first, so the block for height 10 is pushed last.
+        for byte in [3u8, 2, 1] {
+            mock.push(block_with(H256::repeat_byte(0), H256::repeat_byte(byte)))
+                .unwrap();
+        }
+        let eth_node = Provider::new(mock);
+
+        persist_finalized_hashes(&eth_node, &mut database, 9, 12)
+            .await
+            .unwrap();
+
+        let hashes = database.hashes.lock().unwrap();
+        assert_eq!(hashes.get(&10), Some(&H256::repeat_byte(1)));
+        assert_eq!(hashes.get(&11), Some(&H256::repeat_byte(2)));
+        assert_eq!(hashes.get(&12), Some(&H256::repeat_byte(3)));
+    }
+
+    #[tokio::test]
+    async fn persist_finalized_hashes_errors_instead_of_silently_skipping_a_missing_block() {
+        let mut database = FakeDb::default();
+        // Nothing queued: every `get_block` call in range returns `None`,
+        // simulating an endpoint that no longer has that block.
+        let mock = MockProvider::new();
+        let eth_node = Provider::new(mock);
+
+        let result = persist_finalized_hashes(&eth_node, &mut database, 9, 10).await;
+
+        assert!(result.is_err());
+        assert!(database.hashes.lock().unwrap().is_empty());
+    }
+}
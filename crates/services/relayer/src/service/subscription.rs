@@ -0,0 +1,252 @@
+//! Push-based log ingestion over an `eth_subscribe` websocket connection.
+//!
+//! This is an alternative to the poll-and-page HTTP mode: rather than
+//! refetching log pages on a fixed cadence, it re-imports the range
+//! between the finalized DA height and the newest block that has cleared
+//! `da_finalization` confirmations through the
+//! existing reorg-checked, page-based path on connect and again every time
+//! a new head arrives over the subscription. There's deliberately no
+//! separate in-memory log buffer: reusing the same range-import on every
+//! head means there's nothing that can go stale across a reorg or miss
+//! logs minted between reading the head and subscribing.
+
+use super::{
+    get_logs::{
+        download_logs,
+        write_logs,
+    },
+    reorg,
+    state::EthSyncGap,
+    NotifyCaughtUp,
+    NotifySynced,
+};
+use crate::ports::RelayerDb;
+use anyhow::Context;
+use ethers_core::types::H160;
+use ethers_providers::{
+    Middleware,
+    Provider,
+    ProviderError,
+    StreamExt,
+    Ws,
+};
+use fuel_core_services::StateWatcher;
+use fuel_core_types::blockchain::primitives::DaBlockHeight;
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+use tracing::warn;
+
+/// How the relayer ingests logs from the DA layer.
+#[derive(Debug, Clone)]
+pub enum IngestionMode {
+    /// Poll for new log pages every `sync_minimum_duration`.
+    Polling,
+    /// Subscribe to new heads and logs over a websocket connection,
+    /// falling back to [`IngestionMode::Polling`] for the current
+    /// iteration once the socket has dropped `MAX_RECONNECT_ATTEMPTS` times.
+    Subscription {
+        /// Websocket endpoint to subscribe against.
+        ws_url: String,
+    },
+}
+
+/// How long to wait before retrying a dropped subscription.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// How many times the subscription may fail within one
+/// [`run_subscription`] call, counting a failed initial connect and every
+/// later drop (the count is not reset after a connection that worked),
+/// before giving up and letting the caller fall back to polling for this
+/// iteration. Without a cap an endpoint that never comes back would keep
+/// the relayer retrying the websocket forever instead of ever falling back.
+const MAX_RECONNECT_ATTEMPTS: u32 = 3;
+
+/// Run the subscription-based ingestion loop until `watcher` signals a
+/// stop, reconnecting after a transient drop. Returns `Ok` once the
+/// watcher stops; propagates an error once the subscription has failed
+/// [`MAX_RECONNECT_ATTEMPTS`] times in this call, so the caller can fall
+/// back to polling instead of retrying forever.
+pub async fn run_subscription<D>(
+    ws_url: &str,
+    contracts: Vec<H160>,
+    log_page_size: u64,
+    da_finalization: u64,
+    request_timeout: Duration,
+    database: &mut D,
+    synced: &NotifySynced,
+    caught_up: &NotifyCaughtUp,
+    watcher: &mut StateWatcher,
+) -> anyhow::Result<()>
+where
+    D: RelayerDb + 'static,
+{
+    let mut attempts = 0u32;
+    loop {
+        match try_run_subscription(
+            ws_url,
+            contracts.clone(),
+            log_page_size,
+            da_finalization,
+            request_timeout,
+            database,
+            synced,
+            caught_up,
+            watcher,
+        )
+        .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                attempts += 1;
+                warn!(%err, attempts, "eth log subscription dropped");
+                give_up_after_max_attempts(attempts, err)?;
+                tokio::select! {
+                    biased;
+                    _ = watcher.while_started() => return Ok(()),
+                    _ = tokio::time::sleep(RECONNECT_BACKOFF) => {}
+                }
+            }
+        }
+    }
+}
+
+/// Once `attempts` consecutive reconnects have failed, stop retrying and
+/// surface `err` instead, so the caller can fall back to polling rather than
+/// retrying a dead endpoint forever.
+fn give_up_after_max_attempts(attempts: u32, err: anyhow::Error) -> anyhow::Result<()> {
+    if attempts >= MAX_RECONNECT_ATTEMPTS {
+        Err(err)
+            .context("eth log subscription kept dropping after repeated reconnect attempts")
+    } else {
+        Ok(())
+    }
+}
+
+async fn try_run_subscription<D>(
+    ws_url: &str,
+    contracts: Vec<H160>,
+    log_page_size: u64,
+    da_finalization: u64,
+    request_timeout: Duration,
+    database: &mut D,
+    synced: &NotifySynced,
+    caught_up: &NotifyCaughtUp,
+    watcher: &mut StateWatcher,
+) -> anyhow::Result<()>
+where
+    D: RelayerDb + 'static,
+{
+    let provider = Arc::new(Provider::<Ws>::connect(ws_url).await?);
+
+    // Catch the finalized height up to the newest block that has cleared
+    // `da_finalization` confirmations before switching to
+    // the live subscription, through the same path reused below for every
+    // subsequent head. This is the subscription mode's equivalent of
+    // `Task::run_catch_up`, so it signals `caught_up` the same way.
+    import_finalized_range(
+        &provider,
+        contracts.clone(),
+        log_page_size,
+        da_finalization,
+        request_timeout,
+        database,
+        synced,
+    )
+    .await?;
+    if !*caught_up.borrow() {
+        tracing::info!("relayer finished its initial catch-up with the DA layer");
+    }
+    let _ = caught_up.send(true);
+
+    let mut heads = provider.subscribe_blocks().await?;
+    loop {
+        tokio::select! {
+            biased;
+            _ = watcher.while_started() => return Ok(()),
+            head = heads.next() => {
+                head.ok_or_else(|| anyhow::anyhow!("eth head subscription ended"))?;
+                import_finalized_range(
+                    &provider,
+                    contracts.clone(),
+                    log_page_size,
+                    da_finalization,
+                    request_timeout,
+                    database,
+                    synced,
+                ).await?;
+            }
+        }
+    }
+}
+
+/// Import every DA log that has newly cleared `da_finalization`
+/// confirmations, through the same reorg-checked, page-based path used by
+/// the polling mode, and advance the finalized DA height to match —
+/// independent of whether any logs were actually present in the newly
+/// finalized range, so `await_at_least_synced` keeps advancing across
+/// log-free ranges just like the polling path.
+async fn import_finalized_range<P, D>(
+    eth_node: &Arc<P>,
+    contracts: Vec<H160>,
+    log_page_size: u64,
+    da_finalization: u64,
+    request_timeout: Duration,
+    database: &mut D,
+    synced: &NotifySynced,
+) -> anyhow::Result<()>
+where
+    P: Middleware<Error = ProviderError> + 'static,
+    D: RelayerDb + 'static,
+{
+    let finalized = *database.get_finalized_da_height()?;
+    let head = tokio::time::timeout(request_timeout, eth_node.get_block_number())
+        .await
+        .map_err(|_| anyhow::anyhow!("get_block_number timed out after {request_timeout:?}"))??
+        .as_u64();
+    let finalized_head = head.saturating_sub(da_finalization);
+    if finalized_head <= finalized {
+        // Nothing has cleared the finalization depth since the last head.
+        return Ok(())
+    }
+
+    let gap = reorg::reconcile_reorg(
+        eth_node.as_ref(),
+        database,
+        EthSyncGap::new(finalized, finalized_head),
+    )
+    .await?;
+    let logs = download_logs(
+        &gap,
+        contracts,
+        eth_node.clone(),
+        log_page_size,
+        request_timeout,
+    );
+    write_logs(database, logs).await?;
+    database.set_finalized_da_height_to_at_least(&DaBlockHeight::from(gap.latest()))?;
+    reorg::persist_finalized_hashes(eth_node.as_ref(), database, gap.oldest(), gap.latest())
+        .await?;
+    let _ = synced.send(Some(gap.latest().into()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn give_up_after_max_attempts_backs_off_below_the_limit() {
+        for attempts in 1..MAX_RECONNECT_ATTEMPTS {
+            assert!(give_up_after_max_attempts(attempts, anyhow::anyhow!("dropped")).is_ok());
+        }
+    }
+
+    #[test]
+    fn give_up_after_max_attempts_gives_up_once_the_limit_is_reached() {
+        let result = give_up_after_max_attempts(MAX_RECONNECT_ATTEMPTS, anyhow::anyhow!("dropped"));
+
+        assert!(result.is_err());
+    }
+}
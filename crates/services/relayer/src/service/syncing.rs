@@ -0,0 +1,44 @@
+//! Wait for the eth node itself to finish syncing before the relayer
+//! starts reading its state, so it doesn't mistake a node that's still
+//! catching up to the chain head for the chain head itself.
+
+use ethers_core::types::SyncingStatus;
+use ethers_providers::{
+    Middleware,
+    ProviderError,
+};
+use std::time::Duration;
+
+/// Poll `eth_syncing` every `call_frequency` until the eth node reports
+/// it has finished syncing, logging progress every `log_frequency`. Each
+/// individual poll is bounded by `request_timeout` so a wedged endpoint
+/// can't stall the relayer forever.
+pub async fn wait_if_eth_syncing<P>(
+    eth_node: &P,
+    call_frequency: Duration,
+    log_frequency: Duration,
+    request_timeout: Duration,
+) -> anyhow::Result<()>
+where
+    P: Middleware<Error = ProviderError>,
+{
+    let mut since_last_log = log_frequency;
+    loop {
+        let status = tokio::time::timeout(request_timeout, eth_node.syncing())
+            .await
+            .map_err(|_| anyhow::anyhow!("eth_syncing timed out after {request_timeout:?}"))??;
+
+        let progress = match status {
+            SyncingStatus::IsFalse => return Ok(()),
+            SyncingStatus::IsSyncing(progress) => progress,
+        };
+
+        if since_last_log >= log_frequency {
+            tracing::info!(?progress, "waiting for eth node to finish syncing");
+            since_last_log = Duration::ZERO;
+        }
+        since_last_log += call_frequency;
+
+        tokio::time::sleep(call_frequency).await;
+    }
+}
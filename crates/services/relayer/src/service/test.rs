@@ -0,0 +1,121 @@
+//! Tests for the initial catch-up loop's head/finalized gap computation
+//! and its per-page size arithmetic.
+
+use super::*;
+use ethers_core::types::{
+    H256,
+    U64,
+};
+use ethers_providers::{
+    MockProvider,
+    Provider,
+};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct FakeDb {
+    finalized: Mutex<u64>,
+}
+
+impl RelayerDb for FakeDb {
+    fn get_finalized_da_height(&self) -> anyhow::Result<DaBlockHeight> {
+        Ok(DaBlockHeight::from(*self.finalized.lock().unwrap()))
+    }
+
+    fn set_finalized_da_height_to_at_least(
+        &mut self,
+        height: &DaBlockHeight,
+    ) -> anyhow::Result<()> {
+        let mut finalized = self.finalized.lock().unwrap();
+        *finalized = (*finalized).max(u64::from(*height));
+        Ok(())
+    }
+
+    fn get_finalized_da_block_hash(
+        &self,
+        _height: &DaBlockHeight,
+    ) -> anyhow::Result<Option<H256>> {
+        Ok(None)
+    }
+
+    fn set_finalized_da_block_hash(
+        &mut self,
+        _height: &DaBlockHeight,
+        _hash: H256,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn rollback_finalized_da_height_to(&mut self, _height: &DaBlockHeight) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn test_config() -> Config {
+    Config {
+        eth_client_urls: vec![],
+        eth_request_timeout: Duration::from_secs(1),
+        ingestion_mode: IngestionMode::Polling,
+        eth_v2_listening_contracts: vec![],
+        da_deploy_height: DaBlockHeight::from(0u64),
+        da_finalization: DaBlockHeight::from(0u64),
+        log_page_size: 10,
+        sync_minimum_duration: Duration::ZERO,
+        syncing_call_frequency: Duration::from_secs(1),
+        syncing_log_frequency: Duration::from_secs(60),
+        initial_sync_gap_threshold: 5,
+        initial_sync_margin: 1,
+    }
+}
+
+fn task_with(eth_head: u64, finalized: u64, config: Config) -> Task<Provider<MockProvider>, FakeDb> {
+    let mock = MockProvider::new();
+    mock.push(U64::from(eth_head)).unwrap();
+    let eth_node = Provider::new(mock);
+    let (synced, _) = watch::channel(None);
+    let (caught_up, _) = watch::channel(false);
+    Task::new(
+        synced,
+        caught_up,
+        eth_node,
+        FakeDb {
+            finalized: Mutex::new(finalized),
+        },
+        config,
+    )
+}
+
+#[tokio::test]
+async fn remaining_sync_gap_is_the_head_minus_finalization_period_minus_finalized() {
+    let mut config = test_config();
+    config.da_finalization = DaBlockHeight::from(3u64);
+    let task = task_with(100, 20, config);
+
+    let gap = task.remaining_sync_gap().await.unwrap();
+
+    assert_eq!(gap, 100 - 3 - 20);
+}
+
+#[tokio::test]
+async fn remaining_sync_gap_saturates_at_zero_when_nothing_has_cleared_finalization_yet() {
+    let task = task_with(10, 50, test_config());
+
+    let gap = task.remaining_sync_gap().await.unwrap();
+
+    assert_eq!(gap, 0);
+}
+
+#[test]
+fn catch_up_page_end_is_capped_by_log_page_size() {
+    assert_eq!(catch_up_page_end(0, 100, 10), 10);
+}
+
+#[test]
+fn catch_up_page_end_is_capped_by_the_remaining_gap() {
+    assert_eq!(catch_up_page_end(0, 3, 10), 3);
+}
+
+#[test]
+fn catch_up_page_end_is_relative_to_the_finalized_height() {
+    assert_eq!(catch_up_page_end(50, 100, 10), 60);
+}